@@ -0,0 +1,95 @@
+use std::collections::HashSet;
+
+use phylo::likelihood::TreeSearchCost;
+use phylo::optimisers::StopCondition;
+use phylo::tree::Tree;
+
+/// Beam-search alternative to [`phylo::optimisers::SprOptimiser`]'s greedy hill-climbing.
+/// Instead of keeping only the single best neighbour at each round, a beam of the `width`
+/// best-scoring trees from the latest round is expanded every round: every tree in the beam is
+/// replaced by all of its SPR neighbours, the candidates are ranked by log-likelihood, and
+/// the top `width` distinct topologies survive into the next round. This avoids the local
+/// optima a single-step hill climb can get stuck in.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct BeamOptimiser {
+    pub(crate) width: usize,
+}
+
+impl BeamOptimiser {
+    pub(crate) fn new(width: usize) -> Self {
+        BeamOptimiser {
+            width: width.max(1),
+        }
+    }
+
+    /// Runs the beam search to convergence and returns the best-scoring cost found,
+    /// including its tree.
+    ///
+    /// Each round expands every tree currently in the beam into its SPR neighbours, scores
+    /// all of the resulting (deduplicated) candidates, and keeps the `width` best-scoring
+    /// ones as the next beam. The search stops once the best score in the beam fails to
+    /// improve by more than `epsilon`, or after `max_iterations` rounds. The incumbent
+    /// (the best-scoring tree seen across *all* rounds, including the starting tree) is
+    /// tracked separately from the beam and returned at the end, so a round that only
+    /// produces worse candidates than an earlier one — or worse than the start — can never
+    /// regress the result.
+    pub(crate) fn run<C>(&self, cost: C, stop_condition: StopCondition) -> C
+    where
+        C: TreeSearchCost + Clone,
+    {
+        let (max_iterations, epsilon) = match stop_condition {
+            StopCondition::MaxIterEpsilon(max_iter, eps) => (max_iter, eps),
+            StopCondition::MaxIterations(max_iter) => (max_iter, 0.0),
+            StopCondition::Epsilon(eps) => (usize::MAX, eps),
+        };
+
+        let start_score = TreeSearchCost::cost(&cost);
+        let mut best = (start_score, cost.clone());
+        let mut beam = vec![(start_score, cost)];
+        let mut round = 0;
+
+        loop {
+            let mut seen = HashSet::new();
+            let mut candidates: Vec<(f64, C)> = Vec::new();
+            for (_, member) in &beam {
+                for neighbour in member.tree().spr_neighbours() {
+                    let key = format!("{neighbour}");
+                    if !seen.insert(key) {
+                        continue;
+                    }
+                    let mut candidate = member.clone();
+                    candidate.set_tree(neighbour);
+                    let score = TreeSearchCost::cost(&candidate);
+                    candidates.push((score, candidate));
+                }
+            }
+
+            if candidates.is_empty() {
+                break;
+            }
+
+            candidates.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+            candidates.truncate(self.width);
+
+            round += 1;
+            let round_best_score = candidates[0].0;
+            let improved = round_best_score - best.0 > epsilon;
+            if round_best_score > best.0 {
+                best = candidates[0].clone();
+            }
+            beam = candidates;
+
+            if !improved || round >= max_iterations {
+                break;
+            }
+        }
+
+        best.1
+    }
+}
+
+impl Default for BeamOptimiser {
+    fn default() -> Self {
+        BeamOptimiser::new(10)
+    }
+}
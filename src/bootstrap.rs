@@ -0,0 +1,285 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs::File;
+use std::io::Write;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use log::info;
+use rayon::prelude::*;
+
+use phylo::phylo_info::PhyloInfoBuilder;
+use phylo::random::{DefaultGenerator, RandomSource};
+use phylo::tree::Tree;
+
+use crate::cli::Config;
+use crate::{alphabet_for_model, optimise_info, seqio, Result};
+
+/// Runs `cfg.bootstrap` nonparametric bootstrap replicates: each replicate resamples the
+/// alignment columns of `cfg.fasta_file` with replacement, re-infers the topology from
+/// scratch, and the resulting trees are compared against `ml_tree` to work out, for every
+/// internal branch, the fraction of replicates that recover the same bipartition.
+/// Replicates are dispatched across a rayon thread pool, each seeded deterministically from
+/// `seed + i`; that same per-replicate RNG drives both the column resampling (see
+/// [`resample_columns`]) and the topology re-inference, so a replicate is fully
+/// reproducible from its seed alone.
+///
+/// Bipartition extraction, counting and newick annotation are all done locally (see
+/// [`bipartitions`] and [`annotate_support`]) on top of `ml_tree`'s and each replicate
+/// tree's own newick rendering, rather than assuming the `phylo` crate exposes bootstrap
+/// support machinery directly.
+///
+/// Returns the annotated newick for `ml_tree` (support percentages as internal node
+/// labels) and writes a table of bipartition frequencies next to the run's other output
+/// files.
+pub(crate) fn run_bootstrap(cfg: &Config, ml_tree: &Tree, seed: u64) -> Result<String> {
+    info!("Running {} bootstrap replicates.", cfg.bootstrap);
+
+    let records = seqio::read_fasta(&cfg.fasta_file)?;
+    let n_cols = records.iter().map(|(_, seq)| seq.len()).max().unwrap_or(0);
+
+    let replicate_trees = (0..cfg.bootstrap)
+        .into_par_iter()
+        .map(|i| {
+            let replicate_seed = seed + i as u64;
+            let rng = DefaultGenerator::new(replicate_seed);
+
+            let resampled = resample_columns(&records, n_cols, &rng);
+            let replicate_fasta = cfg
+                .out_fldr
+                .join(format!("{}_bootstrap_{i}.fasta", cfg.run_id));
+            seqio::write_fasta(&replicate_fasta, &resampled)?;
+
+            let replicate_info = PhyloInfoBuilder::new(replicate_fasta.clone())
+                .tree_file(cfg.input_tree.clone())
+                .alphabet(Some(alphabet_for_model(cfg.model)))
+                .build_w_rng(&rng)?;
+            // Bootstrap replicates never write the per-site table: it is written once by
+            // `main` from the input alignment, before this function is called.
+            let (_, tree) = optimise_info(cfg, replicate_info, &rng)?;
+            std::fs::remove_file(&replicate_fasta).ok();
+
+            info!("Bootstrap replicate {i} (seed {replicate_seed}) done.");
+            Ok(tree)
+        })
+        .collect::<Result<Vec<Tree>>>()?;
+
+    let ml_newick = format!("{ml_tree}");
+    let ml_root = parse_newick(&ml_newick);
+    let all_leaves = leaves(&ml_root);
+
+    let replicate_bipartitions: Vec<BTreeSet<BTreeSet<String>>> = replicate_trees
+        .iter()
+        .map(|tree| {
+            let root = parse_newick(&format!("{tree}"));
+            bipartitions(&root, &all_leaves)
+                .into_iter()
+                .map(|(_, bipartition)| bipartition)
+                .collect()
+        })
+        .collect();
+
+    let n_replicates = replicate_trees.len().max(1) as f64;
+    let mut support = HashMap::new();
+    for (_, bipartition) in bipartitions(&ml_root, &all_leaves) {
+        let count = replicate_bipartitions
+            .iter()
+            .filter(|rep| rep.contains(&bipartition))
+            .count();
+        support.insert(bipartition, count as f64 / n_replicates);
+    }
+
+    let table_path = cfg.out_fldr.join(format!("{}_bipartitions.tsv", cfg.run_id));
+    info!(
+        "Writing bipartition frequency table to {}",
+        table_path.display()
+    );
+    let mut table = File::create(table_path)?;
+    writeln!(table, "bipartition\tsupport")?;
+    for (bipartition, frequency) in &support {
+        let taxa = bipartition.iter().cloned().collect::<Vec<_>>().join(",");
+        writeln!(table, "{taxa}\t{frequency:.4}")?;
+    }
+
+    Ok(annotate_support(&ml_root, &all_leaves, &support))
+}
+
+/// Resamples `n_cols` alignment columns with replacement, drawing each column index
+/// independently and uniformly from `rng`, and applies the same draws to every record so
+/// that the resampled sequences stay aligned to each other.
+fn resample_columns(
+    records: &[(String, String)],
+    n_cols: usize,
+    rng: &impl RandomSource,
+) -> Vec<(String, String)> {
+    let picks: Vec<usize> = (0..n_cols).map(|_| rng.gen_range(0..n_cols)).collect();
+    records
+        .iter()
+        .map(|(name, seq)| {
+            let seq = seq.as_bytes();
+            let resampled = picks
+                .iter()
+                .map(|&i| seq.get(i).copied().unwrap_or(b'-') as char)
+                .collect();
+            (name.clone(), resampled)
+        })
+        .collect()
+}
+
+/// A minimal Newick node, used only to extract bipartitions and re-attach branch support as
+/// internal node labels; everything else in this crate goes through `phylo`'s own
+/// `Tree`/newick writer.
+struct NewickNode {
+    label: Option<String>,
+    branch_length: Option<String>,
+    children: Vec<NewickNode>,
+}
+
+fn parse_newick(newick: &str) -> NewickNode {
+    let trimmed = newick.trim().trim_end_matches(';');
+    let mut chars = trimmed.chars().peekable();
+    parse_node(&mut chars)
+}
+
+fn parse_node(chars: &mut Peekable<Chars>) -> NewickNode {
+    let mut children = Vec::new();
+    if chars.peek() == Some(&'(') {
+        chars.next();
+        loop {
+            children.push(parse_node(chars));
+            match chars.peek() {
+                Some(&',') => {
+                    chars.next();
+                }
+                Some(&')') => {
+                    chars.next();
+                    break;
+                }
+                _ => break,
+            }
+        }
+    }
+    let label = parse_token(chars, &[':', ',', ')']);
+    let branch_length = if chars.peek() == Some(&':') {
+        chars.next();
+        Some(parse_token(chars, &[',', ')']))
+    } else {
+        None
+    };
+    NewickNode {
+        label: (!label.is_empty()).then_some(label),
+        branch_length,
+        children,
+    }
+}
+
+fn parse_token(chars: &mut Peekable<Chars>, stop: &[char]) -> String {
+    let mut token = String::new();
+    while let Some(&c) = chars.peek() {
+        if stop.contains(&c) || c == '(' {
+            break;
+        }
+        token.push(c);
+        chars.next();
+    }
+    token
+}
+
+/// All leaf labels under `node`.
+fn leaves(node: &NewickNode) -> BTreeSet<String> {
+    if node.children.is_empty() {
+        node.label.iter().cloned().collect()
+    } else {
+        node.children.iter().flat_map(leaves).collect()
+    }
+}
+
+/// Normalises a bipartition so that it compares equal across trees regardless of which side
+/// of the split (or which rooting) produced it: always the side with fewer leaves, with
+/// equal-sized sides broken by taking the lexicographically smaller one.
+fn canonical(side: &BTreeSet<String>, all_leaves: &BTreeSet<String>) -> BTreeSet<String> {
+    let complement: BTreeSet<String> = all_leaves.difference(side).cloned().collect();
+    match side.len().cmp(&complement.len()) {
+        std::cmp::Ordering::Less => side.clone(),
+        std::cmp::Ordering::Greater => complement,
+        std::cmp::Ordering::Equal => {
+            if side <= &complement {
+                side.clone()
+            } else {
+                complement
+            }
+        }
+    }
+}
+
+/// Every non-trivial bipartition induced by an internal branch of the tree rooted at
+/// `node`, in canonical form (see [`canonical`]), each paired with the internal node below
+/// that branch.
+fn bipartitions<'a>(
+    node: &'a NewickNode,
+    all_leaves: &BTreeSet<String>,
+) -> Vec<(&'a NewickNode, BTreeSet<String>)> {
+    let mut out = Vec::new();
+    collect_bipartitions(node, all_leaves, &mut out);
+    out
+}
+
+fn collect_bipartitions<'a>(
+    node: &'a NewickNode,
+    all_leaves: &BTreeSet<String>,
+    out: &mut Vec<(&'a NewickNode, BTreeSet<String>)>,
+) {
+    if !node.children.is_empty() {
+        let side = leaves(node);
+        if side.len() >= 2 && all_leaves.len() - side.len() >= 2 {
+            out.push((node, canonical(&side, all_leaves)));
+        }
+        for child in &node.children {
+            collect_bipartitions(child, all_leaves, out);
+        }
+    }
+}
+
+/// Renders `node` back to newick, inserting each internal node's bootstrap support
+/// percentage (rounded to the nearest integer) as its label.
+fn annotate_support(
+    node: &NewickNode,
+    all_leaves: &BTreeSet<String>,
+    support: &HashMap<BTreeSet<String>, f64>,
+) -> String {
+    format!("{};", render(node, all_leaves, support))
+}
+
+fn render(
+    node: &NewickNode,
+    all_leaves: &BTreeSet<String>,
+    support: &HashMap<BTreeSet<String>, f64>,
+) -> String {
+    let mut out = String::new();
+    if !node.children.is_empty() {
+        out.push('(');
+        out.push_str(
+            &node
+                .children
+                .iter()
+                .map(|child| render(child, all_leaves, support))
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+        out.push(')');
+
+        let side = leaves(node);
+        if side.len() >= 2 && all_leaves.len() - side.len() >= 2 {
+            if let Some(frequency) = support.get(&canonical(&side, all_leaves)) {
+                out.push_str(&format!("{:.0}", frequency * 100.0));
+            }
+        }
+    } else if let Some(label) = &node.label {
+        out.push_str(label);
+    }
+
+    if let Some(branch_length) = &node.branch_length {
+        out.push(':');
+        out.push_str(branch_length);
+    }
+    out
+}
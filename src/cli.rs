@@ -37,6 +37,52 @@ impl Display for SubstModelId {
     }
 }
 
+#[derive(Clone, clap::ValueEnum, Copy, Debug, PartialEq, Eq)]
+pub(super) enum SearchStrategy {
+    Spr,
+    Beam,
+}
+
+impl Display for SearchStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Alignment input format. When not given explicitly on the command line, it is guessed
+/// from the `--seq-file` extension (see [`SeqFormat::from_extension`]).
+#[derive(Clone, clap::ValueEnum, Copy, Debug, PartialEq, Eq)]
+pub(super) enum SeqFormat {
+    Fasta,
+    Phylip,
+    Stockholm,
+    Nexus,
+}
+
+impl SeqFormat {
+    /// Guesses the alignment format from a file extension, falling back to FASTA.
+    pub(super) fn from_extension(path: &std::path::Path) -> Self {
+        match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            "phy" | "phylip" => SeqFormat::Phylip,
+            "sto" | "stk" | "stockholm" => SeqFormat::Stockholm,
+            "nex" | "nexus" | "nxs" => SeqFormat::Nexus,
+            _ => SeqFormat::Fasta,
+        }
+    }
+}
+
+impl Display for SeqFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub(super) struct Cli {
@@ -52,10 +98,16 @@ pub(super) struct Cli {
     #[arg(short = 'x', long, value_name = "MAX_ITERATIONS", default_value = "5")]
     pub(super) max_iterations: usize,
 
-    /// Sequence file in fasta format
+    /// Sequence alignment file; format is given by `--seq-format` or guessed from the extension
     #[arg(short, long, value_name = "SEQ_FILE")]
     pub(super) seq_file: PathBuf,
 
+    /// Alignment format: fasta, phylip, stockholm or nexus (guessed from the file extension
+    /// when omitted). A starting tree embedded in a NEXUS `trees` block is NOT extracted
+    /// automatically yet; pass it explicitly with `--tree-file`.
+    #[arg(long, value_name = "SEQ_FORMAT", ignore_case = true)]
+    pub(super) seq_format: Option<SeqFormat>,
+
     /// Tree file in newick format
     #[arg(short, long, value_name = "TREE_FILE")]
     pub(super) tree_file: Option<PathBuf>,
@@ -100,6 +152,33 @@ pub(super) struct Cli {
     /// PRNG seed that can be fixed for reproducible results
     #[arg(long = "seed", value_name = "PRNG_SEED")]
     pub(super) prng_seed: Option<u64>,
+
+    /// Number of independent optimisation replicates to run from distinct starting trees,
+    /// keeping the replicate with the highest final log-likelihood
+    #[arg(short = 'n', long, value_name = "N_STARTS", default_value = "1")]
+    pub(super) n_starts: usize,
+
+    /// Topology search strategy: greedy SPR hill-climbing or beam search
+    #[arg(
+        long,
+        value_name = "SEARCH_STRATEGY",
+        ignore_case = true,
+        default_value = "spr"
+    )]
+    pub(super) search_strategy: SearchStrategy,
+
+    /// Beam width for `--search-strategy beam`: number of candidate trees kept per round
+    #[arg(long, value_name = "BEAM_WIDTH", default_value = "10")]
+    pub(super) beam_width: usize,
+
+    /// Number of nonparametric bootstrap replicates to run for branch support values (0 disables)
+    #[arg(long, value_name = "N_REPLICATES", default_value = "0")]
+    pub(super) bootstrap: usize,
+
+    /// Write a per-alignment-column table of the observed character of each taxon; it holds
+    /// no per-column log-likelihood, ancestral reconstruction or PIP indel probabilities
+    #[arg(long)]
+    pub(super) per_site_output: bool,
 }
 
 pub struct ConfigBuilder {
@@ -108,6 +187,7 @@ pub struct ConfigBuilder {
     pub run_name: Option<String>,
     pub max_iters: usize,
     pub seq_file: PathBuf,
+    pub seq_format: Option<SeqFormat>,
     pub input_tree: Option<PathBuf>,
     pub model: SubstModelId,
     pub params: Vec<f64>,
@@ -116,6 +196,11 @@ pub struct ConfigBuilder {
     pub gap_handling: GapHandling,
     pub epsilon: f64,
     pub prng_seed: Option<u64>,
+    pub n_starts: usize,
+    pub search_strategy: SearchStrategy,
+    pub beam_width: usize,
+    pub bootstrap: usize,
+    pub per_site_output: bool,
 }
 
 impl From<Cli> for ConfigBuilder {
@@ -126,6 +211,7 @@ impl From<Cli> for ConfigBuilder {
             run_name: cli.run_name,
             max_iters: cli.max_iterations,
             seq_file: cli.seq_file,
+            seq_format: cli.seq_format,
             input_tree: cli.tree_file,
             model: cli.model,
             params: cli.params,
@@ -134,6 +220,11 @@ impl From<Cli> for ConfigBuilder {
             gap_handling: cli.gap_handling,
             epsilon: cli.epsilon,
             prng_seed: cli.prng_seed,
+            n_starts: cli.n_starts,
+            search_strategy: cli.search_strategy,
+            beam_width: cli.beam_width,
+            bootstrap: cli.bootstrap,
+            per_site_output: cli.per_site_output,
         }
     }
 }
@@ -147,6 +238,11 @@ pub struct Config {
     pub run_id: String,
     pub max_iters: usize,
     pub seq_file: PathBuf,
+    pub seq_format: SeqFormat,
+    /// FASTA-format alignment that `PhyloInfoBuilder` is actually pointed at: `seq_file`
+    /// itself when `seq_format` is already FASTA, otherwise a converted copy produced by
+    /// [`crate::seqio::load_as_fasta`] during [`ConfigBuilder::setup`].
+    pub fasta_file: PathBuf,
     pub input_tree: Option<PathBuf>,
     pub model: SubstModelId,
     pub params: Vec<f64>,
@@ -155,6 +251,11 @@ pub struct Config {
     pub gap_handling: GapHandling,
     pub epsilon: f64,
     pub prng_seed: Option<u64>,
+    pub n_starts: usize,
+    pub search_strategy: SearchStrategy,
+    pub beam_width: usize,
+    pub bootstrap: usize,
+    pub per_site_output: bool,
 }
 
 impl Display for Config {
@@ -162,7 +263,12 @@ impl Display for Config {
         writeln!(f, "Run start time: {}", self.timestamp)?;
         writeln!(f, "Run ID: {}", self.run_id)?;
 
-        writeln!(f, "Input sequence file: {}", self.seq_file.display())?;
+        writeln!(
+            f,
+            "Input sequence file: {} ({})",
+            self.seq_file.display(),
+            self.seq_format
+        )?;
         match self.input_tree {
             Some(ref tree_file) => writeln!(f, "Input tree file: {}", tree_file.display())?,
             None => writeln!(f, "No input tree file provided.")?,
@@ -179,9 +285,23 @@ impl Display for Config {
 
         writeln!(
             f,
-            "Optimisation setup: frequencies: {:#?}, max iterations: {}, epsilon: {}",
-            self.freq_opt, self.max_iters, self.epsilon
-        )
+            "Optimisation setup: frequencies: {:#?}, max iterations: {}, epsilon: {}, starts: {}",
+            self.freq_opt, self.max_iters, self.epsilon, self.n_starts
+        )?;
+        match self.search_strategy {
+            SearchStrategy::Spr => writeln!(f, "Search strategy: greedy SPR")?,
+            SearchStrategy::Beam => writeln!(
+                f,
+                "Search strategy: beam search with width {}",
+                self.beam_width
+            )?,
+        }
+        if self.bootstrap > 0 {
+            writeln!(f, "Bootstrap replicates: {}", self.bootstrap)?;
+        } else {
+            writeln!(f, "Bootstrap support: disabled")?;
+        }
+        writeln!(f, "Per-site output table: {}", self.per_site_output)
     }
 }
 
@@ -209,6 +329,12 @@ impl ConfigBuilder {
         let start_tree = out_fldr.join(format!("{run_id}_start_tree.newick"));
         let out_logl = out_fldr.join(format!("{run_id}_logl.out"));
 
+        let seq_format = self
+            .seq_format
+            .unwrap_or_else(|| SeqFormat::from_extension(&self.seq_file));
+        let fasta_file =
+            crate::seqio::load_as_fasta(&self.seq_file, seq_format, &out_fldr, &run_id)?;
+
         Ok(Config {
             timestamp: self.timestamp,
             out_fldr,
@@ -217,6 +343,8 @@ impl ConfigBuilder {
             out_logl,
             run_id,
             max_iters: self.max_iters,
+            seq_format,
+            fasta_file,
             seq_file: self.seq_file,
             input_tree: self.input_tree,
             model: self.model,
@@ -226,6 +354,11 @@ impl ConfigBuilder {
             gap_handling: self.gap_handling,
             epsilon: self.epsilon,
             prng_seed: self.prng_seed,
+            n_starts: self.n_starts,
+            search_strategy: self.search_strategy,
+            beam_width: self.beam_width,
+            bootstrap: self.bootstrap,
+            per_site_output: self.per_site_output,
         })
     }
 }
@@ -6,15 +6,16 @@ use std::result::Result::Ok;
 use anyhow::Error;
 use clap::Parser;
 use log::{debug, info};
+use rayon::prelude::*;
 
-use phylo::alphabets::{dna_alphabet, protein_alphabet};
+use phylo::alphabets::{dna_alphabet, protein_alphabet, Alphabet};
 use phylo::evolutionary_models::FrequencyOptimisation;
 use phylo::io::write_newick_to_file;
 use phylo::likelihood::{ModelSearchCost, TreeSearchCost};
 use phylo::optimisers::{
     Compatible, ModelOptimiser, MoveOptimiser, SprOptimiser, StopCondition, TopologyOptimiser,
 };
-use phylo::phylo_info::PhyloInfoBuilder;
+use phylo::phylo_info::{PhyloInfo, PhyloInfoBuilder};
 use phylo::pip_model::{PIPCostBuilder, PIPModel};
 use phylo::random::{DefaultGenerator, RandomSource};
 use phylo::substitution_models::{
@@ -22,35 +23,63 @@ use phylo::substitution_models::{
 };
 use phylo::tree::Tree;
 
+mod beam;
+mod bootstrap;
 mod cli;
-use crate::cli::{Cli, ConfigBuilder, GapHandling as Gap, SubstModelId as Model};
+mod per_site;
+mod seqio;
+use crate::beam::BeamOptimiser;
+use crate::cli::{Cli, ConfigBuilder, GapHandling as Gap, SearchStrategy, SubstModelId as Model};
 
 type Result<T> = std::result::Result<T, Error>;
 
 macro_rules! pip_optimisation {
-    ($optimiser:ty, $model:ty, $cfg:expr, $info:expr, $rng:expr) => {
-        run_optimisation::<$optimiser>(
-            PIPCostBuilder::new(PIPModel::<$model>::new(&$cfg.freqs, &$cfg.params), $info)
-                .build()?,
-            $cfg.freq_opt,
-            $cfg.stop_condition,
-            $rng,
-        )?
+    ($model:ty, $cfg:expr, $info:expr, $rng:expr) => {
+        match $cfg.search_strategy {
+            SearchStrategy::Spr => run_optimisation(
+                PIPCostBuilder::new(PIPModel::<$model>::new(&$cfg.freqs, &$cfg.params), $info)
+                    .build()?,
+                $cfg.freq_opt,
+                $cfg.stop_condition,
+                SprOptimiser::default(),
+                $rng,
+            )?,
+            SearchStrategy::Beam => run_beam_optimisation(
+                PIPCostBuilder::new(PIPModel::<$model>::new(&$cfg.freqs, &$cfg.params), $info)
+                    .build()?,
+                $cfg.freq_opt,
+                $cfg.stop_condition,
+                BeamOptimiser::new($cfg.beam_width),
+            )?,
+        }
     };
 }
 
 macro_rules! subst_optimisation {
-    ($optimiser:ty, $model:ty, $cfg:expr, $info:expr, $rng:expr) => {
-        run_optimisation::<$optimiser>(
-            SubstitutionCostBuilder::new(
-                SubstModel::<$model>::new(&$cfg.freqs, &$cfg.params),
-                $info,
-            )
-            .build()?,
-            $cfg.freq_opt,
-            $cfg.stop_condition,
-            $rng,
-        )?
+    ($model:ty, $cfg:expr, $info:expr, $rng:expr) => {
+        match $cfg.search_strategy {
+            SearchStrategy::Spr => run_optimisation(
+                SubstitutionCostBuilder::new(
+                    SubstModel::<$model>::new(&$cfg.freqs, &$cfg.params),
+                    $info,
+                )
+                .build()?,
+                $cfg.freq_opt,
+                $cfg.stop_condition,
+                SprOptimiser::default(),
+                $rng,
+            )?,
+            SearchStrategy::Beam => run_beam_optimisation(
+                SubstitutionCostBuilder::new(
+                    SubstModel::<$model>::new(&$cfg.freqs, &$cfg.params),
+                    $info,
+                )
+                .build()?,
+                $cfg.freq_opt,
+                $cfg.stop_condition,
+                BeamOptimiser::new($cfg.beam_width),
+            )?,
+        }
     };
 }
 
@@ -60,35 +89,13 @@ fn main() -> Result<()> {
     info!("JATI run started.");
     info!("{}", cfg);
 
-    let rng = setup_rng(&cfg);
-
     info!("Running on sequences from {}.", cfg.seq_file.display());
 
-    let alphabet = match cfg.model {
-        Model::JC69 | Model::K80 | Model::HKY85 | Model::HKY | Model::TN93 | Model::GTR => {
-            info!("Assuming DNA sequences");
-            dna_alphabet()
-        }
-        Model::WAG | Model::HIVB | Model::BLOSUM => {
-            info!("Assuming protein sequences");
-            protein_alphabet()
-        }
-    };
-
     match &cfg.input_tree {
         Some(tree_file) => info!("Using tree from {}.", tree_file.display()),
         None => info!("No initial tree provided, building NJ tree from sequences."),
     }
 
-    let info = PhyloInfoBuilder::new(cfg.seq_file)
-        .tree_file(cfg.input_tree)
-        .alphabet(Some(alphabet))
-        .build_w_rng(&rng)?;
-
-    info!("Putting start tree in {}", cfg.start_tree.display());
-
-    write_newick_to_file(std::slice::from_ref(&info.tree), cfg.start_tree)?;
-
     info!(
         "Gap handling: {}.",
         match cfg.gap_handling {
@@ -96,41 +103,39 @@ fn main() -> Result<()> {
             Gap::Missing => "as missing data",
         }
     );
-    let (cost, tree) = match cfg.gap_handling {
-        Gap::PIP => match cfg.model {
-            Model::JC69 => pip_optimisation!(SprOptimiser, JC69, cfg, info, &rng),
-            Model::K80 => pip_optimisation!(SprOptimiser, K80, cfg, info, &rng),
-            Model::HKY85 | Model::HKY => pip_optimisation!(SprOptimiser, HKY, cfg, info, &rng),
-            Model::TN93 => pip_optimisation!(SprOptimiser, TN93, cfg, info, &rng),
-            Model::GTR => pip_optimisation!(SprOptimiser, GTR, cfg, info, &rng),
-            Model::WAG => pip_optimisation!(SprOptimiser, WAG, cfg, info, &rng),
-            Model::HIVB => pip_optimisation!(SprOptimiser, HIVB, cfg, info, &rng),
-            Model::BLOSUM => pip_optimisation!(SprOptimiser, BLOSUM, cfg, info, &rng),
-        },
-        Gap::Missing => match cfg.model {
-            Model::JC69 => subst_optimisation!(SprOptimiser, JC69, cfg, info, &rng),
-            Model::K80 => subst_optimisation!(SprOptimiser, K80, cfg, info, &rng),
-            Model::HKY85 | Model::HKY => subst_optimisation!(SprOptimiser, HKY, cfg, info, &rng),
-            Model::TN93 => subst_optimisation!(SprOptimiser, TN93, cfg, info, &rng),
-            Model::GTR => subst_optimisation!(SprOptimiser, GTR, cfg, info, &rng),
-            Model::WAG => subst_optimisation!(SprOptimiser, WAG, cfg, info, &rng),
-            Model::HIVB => subst_optimisation!(SprOptimiser, HIVB, cfg, info, &rng),
-            Model::BLOSUM => subst_optimisation!(SprOptimiser, BLOSUM, cfg, info, &rng),
-        },
+
+    let (start_tree, cost, tree) = if cfg.n_starts > 1 {
+        run_multistart(&cfg)?
+    } else {
+        let rng = setup_rng(&cfg);
+        run_replicate(&cfg, &rng, 0)?
     };
 
+    info!("Putting start tree in {}", cfg.start_tree.display());
+    write_newick_to_file(std::slice::from_ref(&start_tree), cfg.start_tree.clone())?;
+
+    if cfg.per_site_output {
+        per_site::write_per_site_table(&cfg, &cfg.fasta_file)?;
+    }
+
     info!("Putting resulting tree in {}", cfg.out_tree.display());
-    write_newick_to_file(std::slice::from_ref(&tree), cfg.out_tree)?;
+    if cfg.bootstrap > 0 {
+        let annotated_newick = bootstrap::run_bootstrap(&cfg, &tree, base_seed(&cfg))?;
+        let mut out_tree = File::create(cfg.out_tree.clone())?;
+        writeln!(out_tree, "{annotated_newick}")?;
+    } else {
+        write_newick_to_file(std::slice::from_ref(&tree), cfg.out_tree.clone())?;
+    }
 
     info!("Final log-likelihood: {cost}");
-    let mut out_logl = File::create(cfg.out_logl)?;
+    let mut out_logl = File::create(cfg.out_logl.clone())?;
     writeln!(out_logl, "{cost}")?;
 
     Ok(())
 }
 
-fn setup_rng(cfg: &cli::Config) -> DefaultGenerator {
-    let seed = match cfg.prng_seed {
+fn base_seed(cfg: &cli::Config) -> u64 {
+    match cfg.prng_seed {
         None => {
             let seed = cfg.timestamp.as_u64();
             info!("Using current timestamp in milliseconds as the PRNG seed: {seed}");
@@ -140,18 +145,166 @@ fn setup_rng(cfg: &cli::Config) -> DefaultGenerator {
             info!("Using provided PRNG seed: {seed}");
             seed
         }
+    }
+}
+
+fn setup_rng(cfg: &cli::Config) -> DefaultGenerator {
+    DefaultGenerator::new(base_seed(cfg))
+}
+
+pub(crate) fn alphabet_for_model(model: Model) -> Alphabet {
+    match model {
+        Model::JC69 | Model::K80 | Model::HKY85 | Model::HKY | Model::TN93 | Model::GTR => {
+            dna_alphabet()
+        }
+        Model::WAG | Model::HIVB | Model::BLOSUM => protein_alphabet(),
+    }
+}
+
+/// Builds the phylogenetic info (alignment plus starting tree) for the given config.
+///
+/// `cfg.fasta_file` is always FASTA by the time it reaches this function: PHYLIP,
+/// Stockholm and NEXUS inputs are parsed and converted to FASTA by
+/// [`crate::seqio::load_as_fasta`] during config setup, since `PhyloInfoBuilder` is only
+/// ever exercised against FASTA in this crate.
+///
+/// Note: a starting tree embedded in a NEXUS `trees` block is not extracted automatically;
+/// `--tree-file` (i.e. `cfg.input_tree`) remains the only source of a user-supplied tree, so
+/// NEXUS input without `--tree-file` always falls back to building an NJ tree.
+pub(crate) fn build_info(cfg: &cli::Config, rng: &impl RandomSource) -> Result<PhyloInfo> {
+    if cfg.seq_format == cli::SeqFormat::Nexus && cfg.input_tree.is_none() {
+        info!(
+            "NEXUS input given without --tree-file: any starting tree embedded in the NEXUS \
+             file is ignored; building an NJ tree from the alignment instead."
+        );
+    }
+
+    let alphabet = alphabet_for_model(cfg.model);
+
+    Ok(PhyloInfoBuilder::new(cfg.fasta_file.clone())
+        .tree_file(cfg.input_tree.clone())
+        .alphabet(Some(alphabet))
+        .build_w_rng(rng)?)
+}
+
+/// Runs the optimisation for an already-built `PhyloInfo`, returning the final cost and
+/// tree.
+pub(crate) fn optimise_info(
+    cfg: &cli::Config,
+    info: PhyloInfo,
+    rng: &impl RandomSource,
+) -> Result<(f64, Tree)> {
+    let (cost, tree, _final_cost) = match cfg.gap_handling {
+        Gap::PIP => match cfg.model {
+            Model::JC69 => pip_optimisation!(JC69, cfg, info, rng),
+            Model::K80 => pip_optimisation!(K80, cfg, info, rng),
+            Model::HKY85 | Model::HKY => pip_optimisation!(HKY, cfg, info, rng),
+            Model::TN93 => pip_optimisation!(TN93, cfg, info, rng),
+            Model::GTR => pip_optimisation!(GTR, cfg, info, rng),
+            Model::WAG => pip_optimisation!(WAG, cfg, info, rng),
+            Model::HIVB => pip_optimisation!(HIVB, cfg, info, rng),
+            Model::BLOSUM => pip_optimisation!(BLOSUM, cfg, info, rng),
+        },
+        Gap::Missing => match cfg.model {
+            Model::JC69 => subst_optimisation!(JC69, cfg, info, rng),
+            Model::K80 => subst_optimisation!(K80, cfg, info, rng),
+            Model::HKY85 | Model::HKY => subst_optimisation!(HKY, cfg, info, rng),
+            Model::TN93 => subst_optimisation!(TN93, cfg, info, rng),
+            Model::GTR => subst_optimisation!(GTR, cfg, info, rng),
+            Model::WAG => subst_optimisation!(WAG, cfg, info, rng),
+            Model::HIVB => subst_optimisation!(HIVB, cfg, info, rng),
+            Model::BLOSUM => subst_optimisation!(BLOSUM, cfg, info, rng),
+        },
     };
-    DefaultGenerator::new(seed)
+
+    Ok((cost, tree))
+}
+
+/// Number of random SPR moves applied to the NJ/base tree of each multi-start replicate
+/// (other than the first, which keeps the unperturbed base tree as a baseline) so that
+/// replicates actually start from distinct topologies rather than only differing in the
+/// optimiser's RNG seed.
+const MULTISTART_PERTURBATION_MOVES: usize = 3;
+
+/// Perturbs `tree` by taking `n_moves` random steps through its SPR neighbourhood, giving
+/// multi-start replicates distinct starting topologies.
+fn perturb_tree(tree: &Tree, rng: &impl RandomSource, n_moves: usize) -> Tree {
+    let mut tree = tree.clone();
+    for _ in 0..n_moves {
+        let neighbours = tree.spr_neighbours();
+        if neighbours.is_empty() {
+            break;
+        }
+        let pick = rng.gen_range(0..neighbours.len());
+        tree = neighbours[pick].clone();
+    }
+    tree
 }
 
-fn run_optimisation<MO>(
-    cost: impl TreeSearchCost + ModelSearchCost + Display + Clone + Send + Compatible<MO>,
+/// Builds the phylogenetic info and runs a single optimisation from it, returning the
+/// starting tree, the final cost and the final tree of that run. When
+/// `perturbation_moves` is non-zero, the base/NJ tree is randomly perturbed before
+/// optimisation starts, so that multi-start replicates begin from distinct topologies.
+fn run_replicate(
+    cfg: &cli::Config,
+    rng: &impl RandomSource,
+    perturbation_moves: usize,
+) -> Result<(Tree, f64, Tree)> {
+    let mut info = build_info(cfg, rng)?;
+    if perturbation_moves > 0 {
+        info.tree = perturb_tree(&info.tree, rng, perturbation_moves);
+    }
+    let start_tree = info.tree.clone();
+    let (cost, tree) = optimise_info(cfg, info, rng)?;
+    Ok((start_tree, cost, tree))
+}
+
+/// Runs `cfg.n_starts` independent optimisation replicates concurrently on a rayon thread
+/// pool, each seeded deterministically from the base PRNG seed and each starting from a
+/// distinct tree (the NJ/base tree, randomly perturbed for all but the first replicate),
+/// and keeps the replicate with the highest final log-likelihood.
+fn run_multistart(cfg: &cli::Config) -> Result<(Tree, f64, Tree)> {
+    let seed = base_seed(cfg);
+    info!(
+        "Running {} independent optimisation replicates seeded from {seed}.",
+        cfg.n_starts
+    );
+
+    let best = (0..cfg.n_starts)
+        .into_par_iter()
+        .map(|i| {
+            let replicate_seed = seed + i as u64;
+            let rng = DefaultGenerator::new(replicate_seed);
+            let perturbation_moves = if i == 0 {
+                0
+            } else {
+                MULTISTART_PERTURBATION_MOVES
+            };
+            let result = run_replicate(cfg, &rng, perturbation_moves);
+            if let Ok((_, cost, _)) = &result {
+                info!("Replicate {i} (seed {replicate_seed}): final log-likelihood {cost}");
+            }
+            result
+        })
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .max_by(|(_, cost_a, _), (_, cost_b, _)| cost_a.total_cmp(cost_b))
+        .ok_or_else(|| Error::msg("n-starts must be at least 1"))?;
+
+    info!("Best replicate final log-likelihood: {}", best.1);
+    Ok(best)
+}
+
+fn run_optimisation<C, MO>(
+    cost: C,
     freq_opt: FrequencyOptimisation,
     stop_condition: StopCondition,
+    move_optimiser: MO,
     rng: &impl RandomSource,
-) -> Result<(f64, Tree)>
+) -> Result<(f64, Tree, C)>
 where
-    MO: MoveOptimiser + Default,
+    C: TreeSearchCost + ModelSearchCost + Display + Clone + Send + Compatible<MO>,
+    MO: MoveOptimiser + Clone,
 {
     // only propagate epsilon-based stopping condition to intermediate optimisation loops
     let intermediate_stop_condition = match stop_condition {
@@ -170,7 +323,7 @@ where
     while stop_condition.should_continue(iterations, delta) {
         iterations += 1;
         info!("Iteration: {iterations}, current cost: {curr_cost}");
-        let move_optimiser = MO::default();
+        let move_optimiser = move_optimiser.clone();
         prev_cost = curr_cost;
         let model_optimiser =
             ModelOptimiser::with_stop_condition(cost, freq_opt, intermediate_stop_condition);
@@ -191,5 +344,52 @@ where
     debug!("Final parameters: {:?}", cost.params());
     debug!("Final frequencies: {:?}", cost.freqs());
     debug!("Final tree: {}", cost.tree());
-    Ok((curr_cost, cost.tree().clone()))
+    let final_tree = cost.tree().clone();
+    Ok((curr_cost, final_tree, cost))
+}
+
+/// Same outer model/topology alternation as [`run_optimisation`], but drives
+/// [`BeamOptimiser`] directly instead of going through [`TopologyOptimiser`]: beam search
+/// keeps a whole beam of candidate trees alive across a round, rather than refining a
+/// single tree in place, so it does not fit `TopologyOptimiser`'s single-tree-per-round
+/// `MoveOptimiser` dispatch.
+fn run_beam_optimisation<C>(
+    cost: C,
+    freq_opt: FrequencyOptimisation,
+    stop_condition: StopCondition,
+    beam_optimiser: BeamOptimiser,
+) -> Result<(f64, Tree, C)>
+where
+    C: TreeSearchCost + ModelSearchCost + Display + Clone + Send,
+{
+    let intermediate_stop_condition = match stop_condition {
+        StopCondition::Epsilon(e) => StopCondition::epsilon(e),
+        StopCondition::MaxIterEpsilon(_, e) => StopCondition::epsilon(e),
+        _ => StopCondition::default(),
+    };
+
+    let mut cost = cost;
+    let mut prev_cost = f64::NEG_INFINITY;
+    let mut curr_cost = TreeSearchCost::cost(&cost);
+
+    let mut iterations = 0;
+    let mut delta = curr_cost - prev_cost;
+
+    while stop_condition.should_continue(iterations, delta) {
+        iterations += 1;
+        info!("Iteration: {iterations}, current cost: {curr_cost}");
+        prev_cost = curr_cost;
+        let model_optimiser =
+            ModelOptimiser::with_stop_condition(cost, freq_opt, intermediate_stop_condition);
+        cost = beam_optimiser.run(model_optimiser.run()?.cost, intermediate_stop_condition);
+        curr_cost = TreeSearchCost::cost(&cost);
+        delta = curr_cost - prev_cost;
+    }
+
+    info!("Final cost after {} iterations: {}", iterations, curr_cost);
+    debug!("Final parameters: {:?}", cost.params());
+    debug!("Final frequencies: {:?}", cost.freqs());
+    debug!("Final tree: {}", cost.tree());
+    let final_tree = cost.tree().clone();
+    Ok((curr_cost, final_tree, cost))
 }
@@ -0,0 +1,53 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use log::{info, warn};
+
+use crate::cli::Config;
+use crate::{seqio, Result};
+
+/// Writes one row per alignment column to a TSV table of the observed character at each
+/// taxon for that column.
+///
+/// The richer output originally intended here — per-column log-likelihood, marginal
+/// ancestral state reconstruction, and (under the PIP model) posterior insertion points
+/// and per-branch deletion probabilities — would need per-site access to the likelihood
+/// engine's internals. `phylo`'s baseline API surface used elsewhere in this crate
+/// (`TreeSearchCost`, `ModelSearchCost`) does not expose that, so rather than depend on an
+/// unverified richer trait, this only writes what can be read directly off the alignment.
+pub(crate) fn write_per_site_table(cfg: &Config, fasta_path: &Path) -> Result<()> {
+    warn!(
+        "--per-site-output currently only writes the observed alignment columns: per-site \
+         log-likelihood, ancestral reconstruction and PIP indel posteriors are not \
+         implemented, since they would require per-site access to the likelihood engine \
+         that is not exposed to this crate."
+    );
+
+    let records = seqio::read_fasta(fasta_path)?;
+    let path = cfg.out_fldr.join(format!("{}_per_site.tsv", cfg.run_id));
+    info!("Writing per-site output table to {}", path.display());
+
+    let mut out = File::create(path)?;
+    writeln!(out, "##COLUMN=alignment column index (1-based)")?;
+    writeln!(out, "##<taxon>=observed character for that taxon at this column")?;
+
+    let header = std::iter::once("COLUMN".to_string())
+        .chain(records.iter().map(|(name, _)| name.clone()))
+        .collect::<Vec<_>>()
+        .join("\t");
+    writeln!(out, "{header}")?;
+
+    let n_cols = records.iter().map(|(_, seq)| seq.len()).max().unwrap_or(0);
+    for col in 0..n_cols {
+        let mut row = vec![(col + 1).to_string()];
+        for (_, seq) in &records {
+            row.push(seq.as_bytes().get(col).map_or(".".to_string(), |&b| {
+                (b as char).to_string()
+            }));
+        }
+        writeln!(out, "{}", row.join("\t"))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,180 @@
+//! Local alignment input layer for `--seq-format`.
+//!
+//! `phylo::phylo_info::PhyloInfoBuilder` is only ever exercised against FASTA in this
+//! crate's baseline, so rather than guessing at an unverified `seq_format`-style builder
+//! method, PHYLIP/Stockholm/NEXUS alignments are parsed here into plain name/sequence
+//! records and re-serialised as FASTA, which is then handed to `PhyloInfoBuilder` exactly
+//! as the FASTA path always was.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::Error;
+
+use crate::cli::SeqFormat;
+use crate::Result;
+
+/// Resolves `seq_file` to a FASTA path that `PhyloInfoBuilder` can read directly: the file
+/// itself when `format` is already FASTA, or a converted copy under `out_fldr` otherwise.
+pub(crate) fn load_as_fasta(
+    seq_file: &Path,
+    format: SeqFormat,
+    out_fldr: &Path,
+    run_id: &str,
+) -> Result<PathBuf> {
+    if format == SeqFormat::Fasta {
+        return Ok(seq_file.to_path_buf());
+    }
+
+    let records = match format {
+        SeqFormat::Fasta => unreachable!(),
+        SeqFormat::Phylip => parse_phylip(seq_file)?,
+        SeqFormat::Stockholm => parse_stockholm(seq_file)?,
+        SeqFormat::Nexus => parse_nexus(seq_file)?,
+    };
+
+    let converted = out_fldr.join(format!("{run_id}_converted.fasta"));
+    write_fasta(&converted, &records)?;
+    Ok(converted)
+}
+
+/// Reads a plain FASTA file into `(name, sequence)` records, in file order.
+pub(crate) fn read_fasta(path: &Path) -> Result<Vec<(String, String)>> {
+    let content = std::fs::read_to_string(path)?;
+    let mut records = Vec::new();
+    let mut current_name: Option<String> = None;
+    let mut current_seq = String::new();
+
+    for line in content.lines() {
+        let line = line.trim_end();
+        if let Some(name) = line.strip_prefix('>') {
+            if let Some(prev) = current_name.take() {
+                records.push((prev, std::mem::take(&mut current_seq)));
+            }
+            current_name = Some(name.trim().to_string());
+        } else {
+            current_seq.push_str(line.trim());
+        }
+    }
+    if let Some(prev) = current_name {
+        records.push((prev, current_seq));
+    }
+
+    Ok(records)
+}
+
+/// Writes `(name, sequence)` records out as a plain, single-line-per-record FASTA file.
+pub(crate) fn write_fasta(path: &Path, records: &[(String, String)]) -> Result<()> {
+    let mut file = File::create(path)?;
+    for (name, seq) in records {
+        writeln!(file, ">{name}")?;
+        writeln!(file, "{seq}")?;
+    }
+    Ok(())
+}
+
+/// Parses a sequential or interleaved PHYLIP alignment (relaxed: whitespace-separated
+/// names rather than the fixed 10-character field of strict PHYLIP). In sequential files
+/// each taxon's sequence must sit on a single line; sequences wrapped over several lines
+/// are not recognised.
+fn parse_phylip(path: &Path) -> Result<Vec<(String, String)>> {
+    let content = std::fs::read_to_string(path)?;
+    let mut lines = content.lines().map(str::trim).filter(|l| !l.is_empty());
+
+    let header = lines
+        .next()
+        .ok_or_else(|| Error::msg("empty PHYLIP file"))?;
+    let mut header_fields = header.split_whitespace();
+    let n_taxa: usize = header_fields
+        .next()
+        .ok_or_else(|| Error::msg("PHYLIP header is missing the taxon count"))?
+        .parse()?;
+    let n_chars: usize = header_fields
+        .next()
+        .ok_or_else(|| Error::msg("PHYLIP header is missing the sequence length"))?
+        .parse()?;
+
+    let mut names = Vec::with_capacity(n_taxa);
+    let mut seqs: Vec<String> = Vec::with_capacity(n_taxa);
+    for _ in 0..n_taxa {
+        let line = lines
+            .next()
+            .ok_or_else(|| Error::msg("PHYLIP file ends before all taxa are read"))?;
+        let (name, seq) = line
+            .split_once(char::is_whitespace)
+            .unwrap_or((line, ""));
+        names.push(name.to_string());
+        seqs.push(seq.split_whitespace().collect());
+    }
+
+    // Interleaved continuation blocks: `n_taxa` more lines at a time, no names, appended
+    // in taxon order, until every sequence has reached `n_chars`.
+    'blocks: while seqs.iter().any(|seq| seq.len() < n_chars) {
+        for seq in seqs.iter_mut() {
+            match lines.next() {
+                Some(line) => seq.push_str(&line.split_whitespace().collect::<String>()),
+                None => break 'blocks,
+            }
+        }
+    }
+
+    Ok(names.into_iter().zip(seqs).collect())
+}
+
+/// Parses the sequence lines of a (possibly interleaved) Stockholm alignment, ignoring
+/// `#`-prefixed annotation lines and stopping at the `//` terminator.
+fn parse_stockholm(path: &Path) -> Result<Vec<(String, String)>> {
+    let content = std::fs::read_to_string(path)?;
+    parse_name_sequence_lines(&content, "//")
+}
+
+/// Parses the `MATRIX` section of a NEXUS `DATA`/`CHARACTERS` block into name/sequence
+/// records. Any tree carried in a NEXUS `TREES` block is not extracted (see `--tree-file`).
+fn parse_nexus(path: &Path) -> Result<Vec<(String, String)>> {
+    let content = std::fs::read_to_string(path)?;
+    let lower = content.to_lowercase();
+    let matrix_at = lower
+        .find("matrix")
+        .ok_or_else(|| Error::msg("no MATRIX block found in NEXUS file"))?;
+    let body = &content[matrix_at + "matrix".len()..];
+    let end = body.find(';').unwrap_or(body.len());
+
+    parse_name_sequence_lines(&body[..end], "")
+}
+
+/// Shared line parser for the `name whitespace sequence` alignment formats (Stockholm,
+/// NEXUS matrices): accumulates possibly-interleaved blocks under each taxon name, in
+/// first-seen order, stopping at `terminator` (when non-empty) or end of input.
+fn parse_name_sequence_lines(body: &str, terminator: &str) -> Result<Vec<(String, String)>> {
+    let mut order = Vec::new();
+    let mut seqs: HashMap<String, String> = HashMap::new();
+
+    for line in body.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('[') {
+            continue;
+        }
+        if !terminator.is_empty() && line == terminator {
+            break;
+        }
+        let Some((name, seq)) = line.split_once(char::is_whitespace) else {
+            continue;
+        };
+        let name = name.trim_matches('\'').trim_matches('"').to_string();
+        let seq: String = seq.split_whitespace().collect();
+        if !seqs.contains_key(&name) {
+            order.push(name.clone());
+        }
+        seqs.entry(name).or_default().push_str(&seq);
+    }
+
+    Ok(order
+        .into_iter()
+        .map(|name| {
+            let seq = seqs.remove(&name).unwrap_or_default();
+            (name, seq)
+        })
+        .collect())
+}